@@ -2,9 +2,16 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Config {
+    pub devices: Vec<DeviceConfig>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct DeviceConfig {
     pub device: String,
     pub warning_level: WarningLevelConfig,
     pub state: StateConfig,
+    pub thresholds: ThresholdsConfig,
+    pub upper_threshold: UpperThresholdConfig,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -28,10 +35,74 @@ pub struct StateConfig {
     pub pending_discharge: EventConfig,
 }
 
+/// Polling-based percentage thresholds, independent of UPower's own
+/// (coarse, vendor-defined) `WarningLevel` signal.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ThresholdsConfig {
+    /// How often, in seconds, to poll `percentage()` for threshold crossings.
+    pub refresh_interval: u64,
+    pub entries: Vec<ThresholdConfig>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ThresholdConfig {
+    pub percentage: u8,
+    pub event: EventConfig,
+}
+
+impl Default for ThresholdsConfig {
+    fn default() -> Self {
+        Self {
+            refresh_interval: 60,
+            entries: Vec::new(),
+        }
+    }
+}
+
+/// Fires once when `percentage()` rises above `percentage` while charging,
+/// e.g. to remind the user to unplug once the battery reaches a preferred
+/// charge limit. Re-arms once the device leaves the `Charging` state or the
+/// percentage drops back below the bound.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct UpperThresholdConfig {
+    pub percentage: u8,
+    pub event: EventConfig,
+}
+
+impl Default for UpperThresholdConfig {
+    fn default() -> Self {
+        Self {
+            percentage: 80,
+            event: EventConfig::default(),
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Default)]
 pub struct EventConfig {
     pub notification: NotificationConfig,
     pub exec: ExecConfig,
+    pub action: ActionConfig,
+    /// Re-fire this event's notification/exec every `repeat_interval`
+    /// seconds for as long as the condition that triggered it still holds
+    /// (e.g. a recurring low-battery reminder); the `action` runs only on
+    /// the first firing. `0` disables repeating and fires the event once,
+    /// which is the default.
+    pub repeat_interval: u64,
+}
+
+/// A power action to take via `org.freedesktop.login1.Manager`, as a
+/// typed alternative to stuffing `systemctl suspend`/`poweroff` into
+/// `exec.commands`.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ActionConfig {
+    #[default]
+    None,
+    Suspend,
+    Hibernate,
+    HybridSleep,
+    PowerOff,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -82,6 +153,14 @@ impl From<&UrgencyConfig> for notify_rust::Urgency {
 }
 
 impl Default for Config {
+    fn default() -> Self {
+        Self {
+            devices: vec![DeviceConfig::default()],
+        }
+    }
+}
+
+impl Default for DeviceConfig {
     fn default() -> Self {
         Self {
             device: "/org/freedesktop/UPower/devices/battery_BAT0".to_owned(),
@@ -91,6 +170,7 @@ impl Default for Config {
                 discharging: EventConfig::default(),
                 low: EventConfig {
                     exec: ExecConfig::default(),
+                    action: ActionConfig::None,
                     notification: NotificationConfig {
                         enable: true,
                         summary: "Battery low".into(),
@@ -99,9 +179,11 @@ impl Default for Config {
                         timeout: 30000,
                         urgency: UrgencyConfig::Normal,
                     },
+                    repeat_interval: 0,
                 },
                 critical: EventConfig {
                     exec: ExecConfig::default(),
+                    action: ActionConfig::None,
                     notification: NotificationConfig {
                         enable: true,
                         summary: "Battery critically low".into(),
@@ -110,9 +192,11 @@ impl Default for Config {
                         timeout: 0,
                         urgency: UrgencyConfig::Critical,
                     },
+                    repeat_interval: 0,
                 },
                 action: EventConfig {
                     exec: ExecConfig::default(),
+                    action: ActionConfig::PowerOff,
                     notification: NotificationConfig {
                         enable: true,
                         summary: "Battery critically low".into(),
@@ -121,9 +205,12 @@ impl Default for Config {
                         timeout: 0,
                         urgency: UrgencyConfig::Critical,
                     },
+                    repeat_interval: 0,
                 },
             },
-            state: StateConfig::default()
+            state: StateConfig::default(),
+            thresholds: ThresholdsConfig::default(),
+            upper_threshold: UpperThresholdConfig::default(),
         }
     }
 }
@@ -1,7 +1,7 @@
 pub mod config;
 
 use {
-    crate::config::Config,
+    crate::config::{ActionConfig, Config, DeviceConfig, EventConfig},
     anyhow::{Context, Result},
     clap::Parser,
     env_logger::Env,
@@ -9,10 +9,14 @@ use {
         Figment,
         providers::{Format, Serialized, Toml},
     },
-    futures::stream::StreamExt,
+    futures::{
+        future::{AbortHandle, Abortable},
+        stream::{BoxStream, SelectAll, StreamExt, select_all},
+    },
     log::{debug, error, info},
     notify_rust::{Notification, NotificationHandle, Timeout},
     std::{path::PathBuf, process::Command, time::Duration},
+    tokio_stream::wrappers::IntervalStream,
     zbus::{Connection, proxy, zvariant::OwnedValue},
 };
 
@@ -25,7 +29,7 @@ struct Args {
     config: Option<String>,
 }
 
-#[derive(Debug, OwnedValue)]
+#[derive(Debug, Clone, Copy, OwnedValue)]
 #[repr(u32)]
 pub enum WarningLevel {
     Unknown = 0,
@@ -36,7 +40,7 @@ pub enum WarningLevel {
     Action = 5,
 }
 
-#[derive(Debug, OwnedValue)]
+#[derive(Debug, Clone, Copy, OwnedValue)]
 #[repr(u32)]
 pub enum State {
     Unknown = 0,
@@ -48,6 +52,55 @@ pub enum State {
     PendingDischarge = 6,
 }
 
+impl std::fmt::Display for State {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Unknown => "unknown",
+            Self::Charging => "charging",
+            Self::Discharging => "discharging",
+            Self::Empty => "empty",
+            Self::FullyCharged => "fully charged",
+            Self::PendingCharge => "pending charge",
+            Self::PendingDischarge => "pending discharge",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Identifies which per-device notification a repeating heartbeat timer
+/// belongs to, so a tick can be matched back to the `EventConfig` it
+/// should re-fire and the `NotificationHandle` it should replace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EventSlot {
+    Warning,
+    State,
+    Threshold(usize),
+    UpperThreshold,
+}
+
+fn warning_level_event(device_cfg: &DeviceConfig, level: WarningLevel) -> &EventConfig {
+    match level {
+        WarningLevel::Unknown => &device_cfg.warning_level.unknown,
+        WarningLevel::None => &device_cfg.warning_level.none,
+        WarningLevel::Discharging => &device_cfg.warning_level.discharging,
+        WarningLevel::Low => &device_cfg.warning_level.low,
+        WarningLevel::Critical => &device_cfg.warning_level.critical,
+        WarningLevel::Action => &device_cfg.warning_level.action,
+    }
+}
+
+fn state_event(device_cfg: &DeviceConfig, state: State) -> &EventConfig {
+    match state {
+        State::Unknown => &device_cfg.state.unknown,
+        State::Charging => &device_cfg.state.charging,
+        State::Discharging => &device_cfg.state.discharging,
+        State::Empty => &device_cfg.state.empty,
+        State::FullyCharged => &device_cfg.state.fully_charged,
+        State::PendingCharge => &device_cfg.state.pending_charge,
+        State::PendingDischarge => &device_cfg.state.pending_discharge,
+    }
+}
+
 #[proxy(
     interface = "org.freedesktop.UPower.Device",
     default_service = "org.freedesktop.UPower",
@@ -59,11 +112,37 @@ pub trait Device {
     #[zbus(property)]
     fn time_to_empty(&self) -> zbus::Result<i64>;
     #[zbus(property)]
+    fn time_to_full(&self) -> zbus::Result<i64>;
+    #[zbus(property)]
+    fn energy(&self) -> zbus::Result<f64>;
+    #[zbus(property)]
+    fn energy_full(&self) -> zbus::Result<f64>;
+    #[zbus(property)]
+    fn temperature(&self) -> zbus::Result<f64>;
+    #[zbus(property)]
+    fn vendor(&self) -> zbus::Result<String>;
+    #[zbus(property)]
+    fn model(&self) -> zbus::Result<String>;
+    #[zbus(property)]
+    fn icon_name(&self) -> zbus::Result<String>;
+    #[zbus(property)]
     fn warning_level(&self) -> zbus::Result<WarningLevel>;
     #[zbus(property)]
     fn state(&self) -> zbus::Result<State>;
 }
 
+#[proxy(
+    interface = "org.freedesktop.login1.Manager",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1"
+)]
+pub trait Login1Manager {
+    fn suspend(&self, interactive: bool) -> zbus::Result<()>;
+    fn hibernate(&self, interactive: bool) -> zbus::Result<()>;
+    fn hybrid_sleep(&self, interactive: bool) -> zbus::Result<()>;
+    fn power_off(&self, interactive: bool) -> zbus::Result<()>;
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
@@ -83,49 +162,211 @@ async fn main() -> Result<()> {
         .extract()?;
 
     debug!("Config loaded: {config:#?}");
-    info!("Using device {}", config.device);
+    if config.devices.is_empty() {
+        anyhow::bail!("no devices configured");
+    }
 
     let connection = Connection::system().await?;
-    let upower = DeviceProxy::new(&connection, config.device).await?;
-    let mut warning_stream = upower.receive_warning_level_changed().await;
-    let mut state_stream = upower.receive_state_changed().await;
-    let mut warning_notification: Option<NotificationHandle> = None;
-    let mut state_notification: Option<NotificationHandle> = None;
+    let login1 = Login1ManagerProxy::new(&connection).await?;
 
-    let parse_timeout = |t: u32| match t {
-        0 => Timeout::Never,
-        ms => Timeout::Milliseconds(ms),
-    };
+    let mut devices = Vec::with_capacity(config.devices.len());
+    let mut warning_streams = Vec::with_capacity(config.devices.len());
+    let mut state_streams = Vec::with_capacity(config.devices.len());
+    let mut threshold_streams = Vec::with_capacity(config.devices.len());
+
+    for (idx, device_cfg) in config.devices.iter().enumerate() {
+        info!("Using device {}", device_cfg.device);
+        let proxy = DeviceProxy::new(&connection, device_cfg.device.clone()).await?;
+
+        warning_streams.push(
+            proxy
+                .receive_warning_level_changed()
+                .await
+                .map(move |msg| (idx, msg))
+                .boxed(),
+        );
+        state_streams.push(
+            proxy
+                .receive_state_changed()
+                .await
+                .map(move |msg| (idx, msg))
+                .boxed(),
+        );
+        threshold_streams.push(
+            IntervalStream::new(tokio::time::interval(Duration::from_secs(
+                device_cfg.thresholds.refresh_interval.max(1),
+            )))
+            .map(move |_| idx)
+            .boxed(),
+        );
+
+        devices.push(DeviceState {
+            config: device_cfg,
+            proxy,
+            warning_notification: None,
+            warning_repeat: None,
+            last_warning_level: None,
+            state_notification: None,
+            state_repeat: None,
+            last_state: None,
+            threshold_triggered: vec![false; device_cfg.thresholds.entries.len()],
+            threshold_notifications: (0..device_cfg.thresholds.entries.len())
+                .map(|_| None)
+                .collect(),
+            threshold_repeats: (0..device_cfg.thresholds.entries.len())
+                .map(|_| None)
+                .collect(),
+            upper_threshold_triggered: false,
+            upper_threshold_notification: None,
+            upper_threshold_repeat: None,
+        });
+    }
+
+    let mut warning_stream = select_all(warning_streams);
+    let mut state_stream = select_all(state_streams);
+    let mut threshold_stream = select_all(threshold_streams);
+    let mut repeat_stream: SelectAll<BoxStream<'static, (usize, EventSlot)>> = SelectAll::new();
 
     loop {
-        let (active_handle, selected_config) = tokio::select! {
-            Some(msg) = warning_stream.next() => {
+        tokio::select! {
+            Some((idx, msg)) = warning_stream.next() => {
                 let event = msg.get().await?;
-                info!("Received event: WarningLevel::{:?}", event);
-                let cfg = match event {
-                    WarningLevel::Unknown => &config.warning_level.unknown,
-                    WarningLevel::None => &config.warning_level.none,
-                    WarningLevel::Discharging => &config.warning_level.discharging,
-                    WarningLevel::Low => &config.warning_level.low,
-                    WarningLevel::Critical => &config.warning_level.critical,
-                    WarningLevel::Action => &config.warning_level.action,
-                };
-                (&mut warning_notification, cfg)
+                let dev = &mut devices[idx];
+                let device_cfg = dev.config;
+                info!("Received event: WarningLevel::{:?} (device: {})", event, device_cfg.device);
+                let cfg = warning_level_event(device_cfg, event);
+                dev.last_warning_level = Some(event);
+                fire_event(cfg, &mut dev.warning_notification, &dev.proxy, &login1, true).await?;
+                rearm_repeat(
+                    &mut repeat_stream,
+                    &mut dev.warning_repeat,
+                    idx,
+                    EventSlot::Warning,
+                    cfg.repeat_interval,
+                );
             }
 
-            Some(msg) = state_stream.next() => {
+            Some((idx, msg)) = state_stream.next() => {
                 let event = msg.get().await?;
-                info!("Received event: State::{:?}", event);
-                let cfg = match event {
-                    State::Unknown => &config.state.unknown,
-                    State::Charging => &config.state.charging,
-                    State::Discharging =>&config.state.discharging,
-                    State::Empty => &config.state.empty,
-                    State::FullyCharged => &config.state.fully_charged,
-                    State::PendingCharge => &config.state.pending_charge,
-                    State::PendingDischarge => &config.state.pending_discharge,
-                };
-                (&mut state_notification, cfg)
+                let dev = &mut devices[idx];
+                let device_cfg = dev.config;
+                info!("Received event: State::{:?} (device: {})", event, device_cfg.device);
+                let cfg = state_event(device_cfg, event);
+                dev.last_state = Some(event);
+                fire_event(cfg, &mut dev.state_notification, &dev.proxy, &login1, true).await?;
+                rearm_repeat(
+                    &mut repeat_stream,
+                    &mut dev.state_repeat,
+                    idx,
+                    EventSlot::State,
+                    cfg.repeat_interval,
+                );
+            }
+
+            Some(idx) = threshold_stream.next() => {
+                let dev = &mut devices[idx];
+                let device_cfg = dev.config;
+                let percentage = dev.proxy.percentage().await?;
+                let state = dev.proxy.state().await?;
+
+                for i in 0..device_cfg.thresholds.entries.len() {
+                    let threshold = &device_cfg.thresholds.entries[i];
+                    let threshold_pct = f64::from(threshold.percentage);
+
+                    if matches!(state, State::Charging) || percentage >= threshold_pct {
+                        dev.threshold_triggered[i] = false;
+                        if let Some(handle) = dev.threshold_repeats[i].take() {
+                            handle.abort();
+                        }
+                    } else if !dev.threshold_triggered[i] {
+                        dev.threshold_triggered[i] = true;
+                        info!(
+                            "Battery crossed below threshold {}% (device: {})",
+                            threshold.percentage, device_cfg.device
+                        );
+                        fire_event(&threshold.event, &mut dev.threshold_notifications[i], &dev.proxy, &login1, true).await?;
+                        rearm_repeat(
+                            &mut repeat_stream,
+                            &mut dev.threshold_repeats[i],
+                            idx,
+                            EventSlot::Threshold(i),
+                            threshold.event.repeat_interval,
+                        );
+                    }
+                }
+
+                let upper_pct = f64::from(device_cfg.upper_threshold.percentage);
+
+                if !matches!(state, State::Charging) || percentage <= upper_pct {
+                    dev.upper_threshold_triggered = false;
+                    if let Some(handle) = dev.upper_threshold_repeat.take() {
+                        handle.abort();
+                    }
+                } else if !dev.upper_threshold_triggered {
+                    dev.upper_threshold_triggered = true;
+                    info!(
+                        "Battery reached upper threshold {}% (device: {})",
+                        device_cfg.upper_threshold.percentage, device_cfg.device
+                    );
+                    fire_event(
+                        &device_cfg.upper_threshold.event,
+                        &mut dev.upper_threshold_notification,
+                        &dev.proxy,
+                        &login1,
+                        true,
+                    )
+                    .await?;
+                    rearm_repeat(
+                        &mut repeat_stream,
+                        &mut dev.upper_threshold_repeat,
+                        idx,
+                        EventSlot::UpperThreshold,
+                        device_cfg.upper_threshold.event.repeat_interval,
+                    );
+                }
+            }
+
+            Some((idx, slot)) = repeat_stream.next() => {
+                let dev = &mut devices[idx];
+                let device_cfg = dev.config;
+                match slot {
+                    EventSlot::Warning => {
+                        if let Some(level) = dev.last_warning_level {
+                            info!("Repeating notification: WarningLevel::{level:?} (device: {})", device_cfg.device);
+                            let cfg = warning_level_event(device_cfg, level);
+                            fire_event(cfg, &mut dev.warning_notification, &dev.proxy, &login1, false).await?;
+                        }
+                    }
+                    EventSlot::State => {
+                        if let Some(state) = dev.last_state {
+                            info!("Repeating notification: State::{state:?} (device: {})", device_cfg.device);
+                            let cfg = state_event(device_cfg, state);
+                            fire_event(cfg, &mut dev.state_notification, &dev.proxy, &login1, false).await?;
+                        }
+                    }
+                    EventSlot::Threshold(i) => {
+                        let threshold = &device_cfg.thresholds.entries[i];
+                        info!(
+                            "Repeating notification: threshold {}% (device: {})",
+                            threshold.percentage, device_cfg.device
+                        );
+                        fire_event(&threshold.event, &mut dev.threshold_notifications[i], &dev.proxy, &login1, false).await?;
+                    }
+                    EventSlot::UpperThreshold => {
+                        info!(
+                            "Repeating notification: upper threshold {}% (device: {})",
+                            device_cfg.upper_threshold.percentage, device_cfg.device
+                        );
+                        fire_event(
+                            &device_cfg.upper_threshold.event,
+                            &mut dev.upper_threshold_notification,
+                            &dev.proxy,
+                            &login1,
+                            false,
+                        )
+                        .await?;
+                    }
+                }
             }
 
             _ = tokio::signal::ctrl_c() => {
@@ -133,47 +374,187 @@ async fn main() -> Result<()> {
                 break;
             }
         };
+    }
 
-        for cmd in &selected_config.exec.commands {
-            info!("Executing: {cmd}");
-            match Command::new("sh").arg("-c").arg(cmd).spawn() {
-                Ok(_) => {}
-                Err(e) => error!("Failed to spawn command '{cmd}': {e}"),
-            };
-        }
+    Ok(())
+}
 
-        if let Some(handle) = active_handle.take() {
-            handle.close();
-        };
+struct DeviceState<'a> {
+    config: &'a DeviceConfig,
+    proxy: DeviceProxy<'a>,
+    warning_notification: Option<NotificationHandle>,
+    warning_repeat: Option<AbortHandle>,
+    last_warning_level: Option<WarningLevel>,
+    state_notification: Option<NotificationHandle>,
+    state_repeat: Option<AbortHandle>,
+    last_state: Option<State>,
+    threshold_triggered: Vec<bool>,
+    threshold_notifications: Vec<Option<NotificationHandle>>,
+    threshold_repeats: Vec<Option<AbortHandle>>,
+    upper_threshold_triggered: bool,
+    upper_threshold_notification: Option<NotificationHandle>,
+    upper_threshold_repeat: Option<AbortHandle>,
+}
+
+fn parse_timeout(t: u32) -> Timeout {
+    match t {
+        0 => Timeout::Never,
+        ms => Timeout::Milliseconds(ms),
+    }
+}
+
+/// Cancels any previously-armed heartbeat for `slot` and, if
+/// `repeat_interval` is non-zero, arms a new one that yields `(idx, slot)`
+/// on the shared `repeat_stream` every `repeat_interval` seconds until it
+/// is aborted again (the initial firing already happened via `fire_event`,
+/// so the first tick is skipped).
+fn rearm_repeat(
+    repeat_stream: &mut SelectAll<BoxStream<'static, (usize, EventSlot)>>,
+    handle: &mut Option<AbortHandle>,
+    idx: usize,
+    slot: EventSlot,
+    repeat_interval: u64,
+) {
+    if let Some(handle) = handle.take() {
+        handle.abort();
+    }
+
+    if repeat_interval == 0 {
+        return;
+    }
+
+    let (abort_handle, abort_registration) = AbortHandle::new_pair();
+    let ticks = IntervalStream::new(tokio::time::interval(Duration::from_secs(repeat_interval)))
+        .skip(1)
+        .map(move |_| (idx, slot));
+    repeat_stream.push(Abortable::new(ticks, abort_registration).boxed());
+    *handle = Some(abort_handle);
+}
 
-        let n_cfg = &selected_config.notification;
-        if n_cfg.enable {
-            info!("Sending notification: {:#?}", n_cfg);
-
-            *active_handle = Some(
-                Notification::new()
-                    .summary(&n_cfg.summary)
-                    .body(&generate_body(&upower, &n_cfg.body).await?)
-                    .icon(&n_cfg.icon)
-                    .timeout(parse_timeout(n_cfg.timeout))
-                    .urgency((&n_cfg.urgency).into())
-                    .show_async()
-                    .await?,
-            );
+async fn fire_event(
+    event: &EventConfig,
+    handle: &mut Option<NotificationHandle>,
+    upower: &DeviceProxy<'_>,
+    login1: &Login1ManagerProxy<'_>,
+    run_action: bool,
+) -> Result<()> {
+    for cmd in &event.exec.commands {
+        info!("Executing: {cmd}");
+        match Command::new("sh").arg("-c").arg(cmd).spawn() {
+            Ok(_) => {}
+            Err(e) => error!("Failed to spawn command '{cmd}': {e}"),
         };
     }
 
+    // A repeating heartbeat only re-emits the notification/exec; re-invoking
+    // a typed action (e.g. Suspend/PowerOff) on every tick would be a
+    // foot-gun if repeat_interval is combined with an action-bearing event.
+    if run_action {
+        match event.action {
+            ActionConfig::None => {}
+            ActionConfig::Suspend => {
+                info!("Suspending system");
+                login1.suspend(false).await?;
+            }
+            ActionConfig::Hibernate => {
+                info!("Hibernating system");
+                login1.hibernate(false).await?;
+            }
+            ActionConfig::HybridSleep => {
+                info!("Hybrid-sleeping system");
+                login1.hybrid_sleep(false).await?;
+            }
+            ActionConfig::PowerOff => {
+                info!("Powering off system");
+                login1.power_off(false).await?;
+            }
+        }
+    }
+
+    if let Some(handle) = handle.take() {
+        handle.close();
+    };
+
+    let n_cfg = &event.notification;
+    if n_cfg.enable {
+        info!("Sending notification: {:#?}", n_cfg);
+
+        *handle = Some(
+            Notification::new()
+                .summary(&n_cfg.summary)
+                .body(&generate_body(upower, &n_cfg.body).await?)
+                .icon(&n_cfg.icon)
+                .timeout(parse_timeout(n_cfg.timeout))
+                .urgency((&n_cfg.urgency).into())
+                .show_async()
+                .await?,
+        );
+    };
+
     Ok(())
 }
 
 async fn generate_body(device: &DeviceProxy<'_>, template: &str) -> Result<String> {
-    let time_val = device.time_to_empty().await?;
     let percentage = device.percentage().await?;
+    let state = device.state().await?;
+    let time_to_empty = device.time_to_empty().await?;
+    let time_to_full = device.time_to_full().await?;
+    let energy = device.energy().await?;
+    let temperature = device.temperature().await?;
+    let vendor = device.vendor().await?;
+    let model = device.model().await?;
+    let icon = device.icon_name().await?;
+
+    // UPower reports 0 for time-to-empty/time-to-full when it hasn't
+    // computed an estimate yet, so pick whichever one is relevant to the
+    // current state and fall back to "unknown" rather than "0 minutes".
+    let time = match state {
+        State::Charging | State::PendingCharge => time_to_full,
+        _ => time_to_empty,
+    };
 
-    let time = Duration::from_secs(time_val as u64);
     Ok(template
-        .replace("{time}", &format_duration(time))
-        .replace("{percentage}", &percentage.to_string()))
+        .replace("{time}", &format_time(time))
+        .replace("{percentage}", &percentage.to_string())
+        .replace("{time_to_full}", &format_time(time_to_full))
+        .replace("{energy}", &format_energy(energy))
+        .replace("{temperature}", &format_temperature(temperature))
+        .replace("{state}", &state.to_string())
+        .replace("{vendor}", &format_unknown(&vendor))
+        .replace("{model}", &format_unknown(&model))
+        .replace("{icon}", &icon))
+}
+
+fn format_time(seconds: i64) -> String {
+    if seconds <= 0 {
+        "unknown".to_owned()
+    } else {
+        format_duration(Duration::from_secs(seconds as u64))
+    }
+}
+
+fn format_energy(energy_wh: f64) -> String {
+    if energy_wh <= 0.0 {
+        "unknown".to_owned()
+    } else {
+        format!("{energy_wh:.1} Wh")
+    }
+}
+
+fn format_temperature(celsius: f64) -> String {
+    if celsius == 0.0 {
+        "unknown".to_owned()
+    } else {
+        format!("{celsius:.1}°C")
+    }
+}
+
+fn format_unknown(value: &str) -> String {
+    if value.is_empty() {
+        "unknown".to_owned()
+    } else {
+        value.to_owned()
+    }
 }
 
 fn format_duration(duration: Duration) -> String {